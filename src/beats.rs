@@ -0,0 +1,345 @@
+// A mutable, in-place layer over the flat `vals` fiber, modeled on "segment
+// tree beats": each node carries (sum, max, second_max, count_max, len), and
+// chmin recurses on a break/tag condition rather than a clean split point.
+//
+// MutableCompressedSparseFiber reuses the fptr/fids rows of a CompressedSparseFiber
+// to resolve a coordinate prefix to a [lo, hi) range via crate::prefix_range.
+
+use std::iter::Sum;
+use std::ops::{Add, Sub};
+
+use crate::CompressedSparseFiber;
+
+#[derive(Debug, Clone, Copy)]
+struct SegNode<T> {
+    sum: T,
+    max: T,
+    // The greatest value in the range strictly less than `max`, or `None`
+    // if every element in the range equals `max`.
+    second_max: Option<T>,
+    count_max: usize,
+    len: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Tag<T> {
+    Assign(T),
+    Chmin(T),
+}
+
+#[derive(Debug, Clone)]
+struct SegmentTreeBeats<T> {
+    nodes: Vec<Option<SegNode<T>>>,
+    tags: Vec<Option<Tag<T>>>,
+    len: usize,
+}
+
+impl<T> SegmentTreeBeats<T>
+    where T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + Sum<T> {
+    fn build(vals: &[T]) -> Self {
+        let len = vals.len();
+        let mut tree = SegmentTreeBeats {
+            nodes: vec![None; 4 * len.max(1)],
+            tags: vec![None; 4 * len.max(1)],
+            len,
+        };
+        if len > 0 {
+            tree.build_node(1, 0, len, vals);
+        }
+        tree
+    }
+
+    fn build_node(&mut self, node: usize, lo: usize, hi: usize, vals: &[T]) {
+        if hi - lo == 1 {
+            self.nodes[node] = Some(SegNode {
+                sum: vals[lo],
+                max: vals[lo],
+                second_max: None,
+                count_max: 1,
+                len: 1,
+            });
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        self.build_node(2 * node, lo, mid, vals);
+        self.build_node(2 * node + 1, mid, hi, vals);
+        self.pull(node);
+    }
+
+    fn pull(&mut self, node: usize) {
+        let l = self.nodes[2 * node].unwrap();
+        let r = self.nodes[2 * node + 1].unwrap();
+        self.nodes[node] = Some(merge(l, r));
+    }
+
+    fn apply_assign(&mut self, node: usize, v: T) {
+        let n = self.nodes[node].as_mut().unwrap();
+        n.sum = std::iter::repeat(v).take(n.len).sum();
+        n.max = v;
+        n.second_max = None;
+        n.count_max = n.len;
+        self.tags[node] = Some(Tag::Assign(v));
+    }
+
+    fn apply_chmin(&mut self, node: usize, v: T) {
+        let n = self.nodes[node].as_mut().unwrap();
+        if v >= n.max {
+            return;
+        }
+        let delta = std::iter::repeat(n.max - v).take(n.count_max).sum::<T>();
+        n.sum = n.sum - delta;
+        n.max = v;
+
+        self.tags[node] = Some(match self.tags[node] {
+            // The whole range is one value; chmin-ing an assign tag just
+            // lowers the assigned value (or is a no-op, handled above).
+            Some(Tag::Assign(_)) => Tag::Assign(v),
+            // Compose with any pending chmin by keeping the tighter bound.
+            Some(Tag::Chmin(existing)) if existing <= v => Tag::Chmin(existing),
+            _ => Tag::Chmin(v),
+        });
+    }
+
+    fn push_down(&mut self, node: usize) {
+        if let Some(tag) = self.tags[node].take() {
+            match tag {
+                Tag::Assign(v) => {
+                    self.apply_assign(2 * node, v);
+                    self.apply_assign(2 * node + 1, v);
+                }
+                Tag::Chmin(v) => {
+                    self.apply_chmin(2 * node, v);
+                    self.apply_chmin(2 * node + 1, v);
+                }
+            }
+        }
+    }
+
+    fn range_assign(&mut self, lo: usize, hi: usize, v: T) {
+        self.update_assign(1, 0, self.len, lo, hi, v);
+    }
+
+    fn update_assign(&mut self, node: usize, node_lo: usize, node_hi: usize, lo: usize, hi: usize, v: T) {
+        if hi <= node_lo || node_hi <= lo {
+            return;
+        }
+        if lo <= node_lo && node_hi <= hi {
+            self.apply_assign(node, v);
+            return;
+        }
+        self.push_down(node);
+        let mid = node_lo + (node_hi - node_lo) / 2;
+        self.update_assign(2 * node, node_lo, mid, lo, hi, v);
+        self.update_assign(2 * node + 1, mid, node_hi, lo, hi, v);
+        self.pull(node);
+    }
+
+    fn range_chmin(&mut self, lo: usize, hi: usize, v: T) {
+        self.update_chmin(1, 0, self.len, lo, hi, v);
+    }
+
+    fn update_chmin(&mut self, node: usize, node_lo: usize, node_hi: usize, lo: usize, hi: usize, v: T) {
+        if hi <= node_lo || node_hi <= lo {
+            return;
+        }
+        let current = self.nodes[node].unwrap();
+        if current.max <= v {
+            return;
+        }
+        let fully_covered = lo <= node_lo && node_hi <= hi;
+        if fully_covered && current.second_max.is_none_or(|s| s < v) {
+            self.apply_chmin(node, v);
+            return;
+        }
+        self.push_down(node);
+        let mid = node_lo + (node_hi - node_lo) / 2;
+        self.update_chmin(2 * node, node_lo, mid, lo, hi, v);
+        self.update_chmin(2 * node + 1, mid, node_hi, lo, hi, v);
+        self.pull(node);
+    }
+
+    fn range_sum(&mut self, lo: usize, hi: usize) -> T {
+        if lo >= hi {
+            return std::iter::empty::<T>().sum();
+        }
+        self.query_sum(1, 0, self.len, lo, hi)
+    }
+
+    fn query_sum(&mut self, node: usize, node_lo: usize, node_hi: usize, lo: usize, hi: usize) -> T {
+        if lo >= hi {
+            return std::iter::empty::<T>().sum();
+        }
+        if lo <= node_lo && node_hi <= hi {
+            return self.nodes[node].unwrap().sum;
+        }
+        self.push_down(node);
+        let mid = node_lo + (node_hi - node_lo) / 2;
+        let mut result = None;
+        if lo < mid {
+            result = Some(self.query_sum(2 * node, node_lo, mid, lo, hi));
+        }
+        if hi > mid {
+            let right = self.query_sum(2 * node + 1, mid, node_hi, lo, hi);
+            result = Some(match result {
+                Some(left) => left + right,
+                None => right,
+            });
+        }
+        result.unwrap()
+    }
+}
+
+// Combine two sibling nodes, tracking max, its count, and the greatest value
+// strictly below it (second_max) — what range_chmin needs to decide whether
+// it can stop at a node or must recurse into its children.
+fn merge<T: Copy + PartialOrd + Add<Output = T>>(l: SegNode<T>, r: SegNode<T>) -> SegNode<T> {
+    let sum = l.sum + r.sum;
+    let len = l.len + r.len;
+    let (max, count_max) = if l.max > r.max {
+        (l.max, l.count_max)
+    } else if r.max > l.max {
+        (r.max, r.count_max)
+    } else {
+        (l.max, l.count_max + r.count_max)
+    };
+
+    let mut second_max = None;
+    for candidate in [Some(l.max), l.second_max, Some(r.max), r.second_max].iter().flatten().copied() {
+        if candidate < max && second_max.is_none_or(|s| candidate > s) {
+            second_max = Some(candidate);
+        }
+    }
+
+    SegNode { sum, max, second_max, count_max, len }
+}
+
+#[derive(Debug, Clone)]
+pub struct MutableCompressedSparseFiber<T, U> {
+    fptr: Vec<Vec<usize>>,
+    fids: Vec<Vec<U>>,
+    tree: SegmentTreeBeats<T>,
+}
+
+impl<T, U> From<CompressedSparseFiber<T, U>> for MutableCompressedSparseFiber<T, U>
+    where T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + Sum<T>,
+          U: Clone {
+    fn from(csf: CompressedSparseFiber<T, U>) -> Self {
+        MutableCompressedSparseFiber {
+            tree: SegmentTreeBeats::build(&csf.vals),
+            fptr: csf.fptr,
+            fids: csf.fids,
+        }
+    }
+}
+
+impl<T, U> MutableCompressedSparseFiber<T, U>
+    where T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T> + Sum<T>,
+          U: Ord {
+    fn resolve(&self, prefix: &[U]) -> Option<(usize, usize)> {
+        crate::prefix_range(&self.fptr, &self.fids, self.tree.len, prefix)
+    }
+
+    // Replace every value under prefix with v. False if prefix names no node.
+    pub fn range_assign(&mut self, prefix: &[U], v: T) -> bool {
+        match self.resolve(prefix) {
+            Some((lo, hi)) => { self.tree.range_assign(lo, hi, v); true }
+            None => false,
+        }
+    }
+
+    // Replace every value under prefix greater than v with v. False if prefix names no node.
+    pub fn range_chmin(&mut self, prefix: &[U], v: T) -> bool {
+        match self.resolve(prefix) {
+            Some((lo, hi)) => { self.tree.range_chmin(lo, hi, v); true }
+            None => false,
+        }
+    }
+
+    // Sum every value under prefix. None if prefix names no node.
+    pub fn range_sum(&mut self, prefix: &[U]) -> Option<T> {
+        let (lo, hi) = self.resolve(prefix)?;
+        Some(self.tree.range_sum(lo, hi))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_csf() -> CompressedSparseFiber<i32, i32> {
+        CompressedSparseFiber::new(
+            vec![vec![0, 2, 3], vec![0, 1, 3, 4], vec![0, 2, 4, 5, 8]],
+            vec![vec![1, 2], vec![1, 2, 2], vec![1, 1, 2, 2], vec![2, 3, 1, 3, 1, 1, 2, 3]],
+            vec![1, 2, 3, 4, 5, 6, 7, 8],
+        )
+    }
+
+    #[test]
+    fn test_range_sum_matches_plain_sum() {
+        let mut tree = SegmentTreeBeats::build(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(36, tree.range_sum(0, 8));
+        assert_eq!(6, tree.range_sum(0, 3));
+        assert_eq!(9, tree.range_sum(3, 5));
+        assert_eq!(8, tree.range_sum(7, 8));
+    }
+
+    #[test]
+    fn test_range_assign() {
+        let mut tree = SegmentTreeBeats::build(&[1, 2, 3, 4, 5]);
+        tree.range_assign(1, 4, 0);
+        assert_eq!(6, tree.range_sum(0, 5));
+        assert_eq!(0, tree.range_sum(1, 4));
+    }
+
+    #[test]
+    fn test_range_chmin() {
+        let mut tree = SegmentTreeBeats::build(&[5, 1, 4, 2, 8, 3]);
+        tree.range_chmin(0, 6, 4);
+        assert_eq!([4, 1, 4, 2, 4, 3].iter().sum::<i32>(), tree.range_sum(0, 6));
+
+        // A second chmin with a smaller bound must still clamp everything.
+        tree.range_chmin(0, 6, 2);
+        assert_eq!([2, 1, 2, 2, 2, 2].iter().sum::<i32>(), tree.range_sum(0, 6));
+    }
+
+    #[test]
+    fn test_range_chmin_no_op_above_max() {
+        let mut tree = SegmentTreeBeats::build(&[1, 2, 3]);
+        tree.range_chmin(0, 3, 100);
+        assert_eq!(6, tree.range_sum(0, 3));
+    }
+
+    #[test]
+    fn test_mutable_csf_range_ops_by_prefix() {
+        let mut x: MutableCompressedSparseFiber<_, _> = sample_csf().into();
+
+        // Prefix [1] covers vals[0..5] = [1, 2, 3, 4, 5].
+        assert_eq!(Some(15), x.range_sum(&[1]));
+
+        assert!(x.range_chmin(&[1], 3));
+        assert_eq!(Some(1 + 2 + 3 + 3 + 3), x.range_sum(&[1]));
+
+        assert!(x.range_assign(&[2], 0));
+        assert_eq!(Some(0), x.range_sum(&[2]));
+
+        assert_eq!(None, x.range_sum(&[9]));
+        assert!(!x.range_assign(&[9], 0));
+    }
+
+    #[test]
+    fn test_range_sum_on_empty_tensor() {
+        let empty = CompressedSparseFiber::new(vec![], vec![vec![]], vec![]);
+        let mut x: MutableCompressedSparseFiber<i32, i32> = empty.into();
+
+        assert_eq!(Some(0), x.range_sum(&[]));
+    }
+
+    #[test]
+    fn test_range_chmin_on_empty_tensor() {
+        let empty = CompressedSparseFiber::new(vec![], vec![vec![]], vec![]);
+        let mut x: MutableCompressedSparseFiber<i32, i32> = empty.into();
+
+        assert!(x.range_chmin(&[], 5));
+        assert_eq!(Some(0), x.range_sum(&[]));
+    }
+}
@@ -3,6 +3,12 @@ use std::hash::Hash;
 use std::iter::Sum;
 use sequence_trie::SequenceTrie;
 
+mod monoid;
+pub use monoid::{IdempotentMonoid, Max, Min, Monoid, Product, Sum as MonoidSum};
+
+mod beats;
+pub use beats::MutableCompressedSparseFiber;
+
 type Row<T, U> = (Vec<U>, T);
 type Rows<T, U> = Vec<Row<T, U>>;
 
@@ -91,6 +97,136 @@ impl<'a, T: 'a, U> CompressedSparseFiber<T, U>
                 .sum::<U>()
         }
     }
+
+    // Like sum_column, but folds through an arbitrary Monoid instead of +.
+    pub fn reduce_column<M>(self: &CompressedSparseFiber<T, U>, col_index: usize) -> M::Item
+        where T: Copy,
+              U: Copy,
+              M: Monoid<Item = U> {
+        let row = &self.fids[col_index];
+
+        if col_index == self.fptr.len() {
+            row.iter().fold(M::unit(), |acc, &x| M::op(acc, x))
+        } else {
+            let w = self.weights(col_index);
+            row.iter()
+                .zip(w)
+                .fold(M::unit(), |acc, (&x, weight)| {
+                    std::iter::repeat_n(x, weight).fold(acc, M::op)
+                })
+        }
+    }
+
+    // op(x, x) == x for an IdempotentMonoid (Max, Min), so unlike reduce_column
+    // this never needs `weights` — each distinct fids[col_index] entry is folded once.
+    pub fn reduce_column_idempotent<M>(self: &CompressedSparseFiber<T, U>, col_index: usize) -> M::Item
+        where U: Copy,
+              M: IdempotentMonoid<Item = U> {
+        self.fids[col_index].iter().fold(M::unit(), |acc, &x| M::op(acc, x))
+    }
+}
+
+// Descend fptr/fids to the node index matching a non-empty prefix. Shared by
+// prefix_range and CompressedSparseFiber::slice.
+fn resolve_node<U: Ord>(fptr: &[Vec<usize>], fids: &[Vec<U>], prefix: &[U]) -> Option<usize> {
+    let mut n = fids[0].binary_search(&prefix[0]).ok()?;
+    for level in 1..prefix.len() {
+        let span_lo = fptr[level - 1][n];
+        let span_hi = fptr[level - 1][n + 1];
+        let offset = fids[level][span_lo..span_hi].binary_search(&prefix[level]).ok()?;
+        n = span_lo + offset;
+    }
+    Some(n)
+}
+
+// Resolve a (possibly partial) prefix to the contiguous [lo, hi) range of `vals`
+// holding every stored value whose first prefix.len() coordinates match. None if
+// `prefix` names no node, or is longer than the tensor's depth.
+pub(crate) fn prefix_range<U: Ord>(
+    fptr: &[Vec<usize>],
+    fids: &[Vec<U>],
+    vals_len: usize,
+    prefix: &[U],
+) -> Option<(usize, usize)> {
+    let depth = fids.len();
+    if prefix.len() > depth {
+        return None;
+    }
+    if prefix.is_empty() {
+        return Some((0, vals_len));
+    }
+
+    let n = resolve_node(fptr, fids, prefix)?;
+    let (mut lo, mut hi) = (n, n + 1);
+    for row in fptr.iter().skip(prefix.len() - 1) {
+        lo = row[lo];
+        hi = row[hi];
+    }
+    Some((lo, hi))
+}
+
+impl<T, U> CompressedSparseFiber<T, U>
+    where U: Clone + Ord {
+    fn prefix_range(self: &CompressedSparseFiber<T, U>, prefix: &[U]) -> Option<(usize, usize)> {
+        prefix_range(&self.fptr, &self.fids, self.vals.len(), prefix)
+    }
+
+    // Sum over the sub-tensor rooted at prefix. None if prefix names no node.
+    pub fn range_reduce(self: &CompressedSparseFiber<T, U>, prefix: &[U]) -> Option<T>
+        where T: Copy + Sum<T> {
+        let (lo, hi) = self.prefix_range(prefix)?;
+        Some(self.vals[lo..hi].iter().copied().sum())
+    }
+
+    // Fetch the value at the full coordinate coord via binary_search descent.
+    pub fn get(self: &CompressedSparseFiber<T, U>, coord: &[U]) -> Option<T>
+        where T: Copy {
+        if coord.len() != self.fids.len() {
+            return None;
+        }
+        let (lo, _) = self.prefix_range(coord)?;
+        Some(self.vals[lo])
+    }
+
+    // Extract the sub-tensor rooted at prefix as a standalone CSF, re-basing
+    // the truncated fptr/fids rows to zero. None if prefix names no node or
+    // covers the full depth (use get for a single leaf).
+    pub fn slice(self: &CompressedSparseFiber<T, U>, prefix: &[U]) -> Option<CompressedSparseFiber<T, U>>
+        where T: Copy {
+        let depth = self.fids.len();
+        if prefix.is_empty() {
+            return Some(self.clone());
+        }
+        if prefix.len() >= depth {
+            return None;
+        }
+
+        let n = resolve_node(&self.fptr, &self.fids, prefix)?;
+
+        let k = prefix.len();
+        let mut range = (self.fptr[k - 1][n], self.fptr[k - 1][n + 1]);
+        let mut new_fids = Vec::with_capacity(depth - k);
+        let mut new_fptr = Vec::with_capacity(depth - k - 1);
+
+        for level in k..depth {
+            if level > k {
+                range = (self.fptr[level - 1][range.0], self.fptr[level - 1][range.1]);
+            }
+            new_fids.push(self.fids[level][range.0..range.1].to_vec());
+            if level < depth - 1 {
+                let base = self.fptr[level][range.0];
+                new_fptr.push(
+                    self.fptr[level][range.0..=range.1]
+                        .iter()
+                        .map(|&x| x - base)
+                        .collect(),
+                );
+            }
+        }
+
+        let vals = self.vals[range.0..range.1].to_vec();
+        Some(CompressedSparseFiber::new(new_fptr, new_fids, vals))
+    }
 }
 
 impl<T, U> From<&SequenceTrie<U, T>> for CompressedSparseFiber<T, U>
@@ -250,4 +386,105 @@ mod tests {
         assert_eq!(expected_sum(&rows, 2), x.sum_column(2));
         assert_eq!(expected_sum(&rows, 3), x.sum_column(3));
     }
+
+    #[test]
+    fn test_reduce_column_sum_matches_sum_column() {
+        let x = sample_csf();
+
+        for col_index in 0..4 {
+            assert_eq!(x.sum_column(col_index), x.reduce_column::<MonoidSum<i32>>(col_index));
+        }
+    }
+
+    #[test]
+    fn test_reduce_column_max_min() {
+        let x = sample_csf();
+        let rows = sample_rows();
+
+        for col_index in 0..4 {
+            let expected_max = rows.iter().map(|(row, _)| row[col_index]).max().unwrap();
+            let expected_min = rows.iter().map(|(row, _)| row[col_index]).min().unwrap();
+
+            assert_eq!(expected_max, x.reduce_column_idempotent::<Max<i32>>(col_index));
+            assert_eq!(expected_min, x.reduce_column_idempotent::<Min<i32>>(col_index));
+        }
+    }
+
+    #[test]
+    fn test_reduce_column_product() {
+        let x = sample_csf();
+
+        // Column 0 has fids [1, 2] with weights [5, 3] (5 rows start with 1, 3 start with 2).
+        assert_eq!(1i32.pow(5) * 2i32.pow(3), x.reduce_column::<Product<i32>>(0));
+    }
+
+    fn expected_range_sum(rows: &Rows<f32, i32>, prefix: &[i32]) -> f32 {
+        rows.iter()
+            .filter(|(row, _)| row.starts_with(prefix))
+            .map(|(_, val)| val)
+            .sum()
+    }
+
+    #[test]
+    fn test_range_reduce() {
+        let x = sample_csf();
+        let rows = sample_rows();
+
+        assert_eq!(Some(expected_range_sum(&rows, &[])), x.range_reduce(&[]));
+        assert_eq!(Some(expected_range_sum(&rows, &[1])), x.range_reduce(&[1]));
+        assert_eq!(Some(expected_range_sum(&rows, &[1, 2])), x.range_reduce(&[1, 2]));
+        assert_eq!(Some(expected_range_sum(&rows, &[1, 2, 1])), x.range_reduce(&[1, 2, 1]));
+        assert_eq!(Some(expected_range_sum(&rows, &[2, 2, 2, 2])), x.range_reduce(&[2, 2, 2, 2]));
+    }
+
+    #[test]
+    fn test_range_reduce_missing_prefix() {
+        let x = sample_csf();
+
+        assert_eq!(None, x.range_reduce(&[3]));
+        assert_eq!(None, x.range_reduce(&[1, 9]));
+        assert_eq!(None, x.range_reduce(&[1, 1, 1, 1, 1]));
+    }
+
+    #[test]
+    fn test_get() {
+        let x = sample_csf();
+        let rows = sample_rows();
+
+        for (coord, val) in &rows {
+            assert_eq!(Some(*val), x.get(coord));
+        }
+
+        assert_eq!(None, x.get(&[1, 1, 1, 4]));
+        assert_eq!(None, x.get(&[3, 1, 1, 1]));
+        assert_eq!(None, x.get(&[1, 1, 1]));
+    }
+
+    #[test]
+    fn test_slice() {
+        let x = sample_csf();
+        let rows = sample_rows();
+
+        let sliced = x.slice(&[1]).unwrap();
+        for (coord, val) in &rows {
+            if coord[0] != 1 {
+                continue;
+            }
+            assert_eq!(Some(*val), sliced.get(&coord[1..]));
+        }
+        assert_eq!(sliced.vals.len(), rows.iter().filter(|(c, _)| c[0] == 1).count());
+
+        assert!(x.slice(&[3]).is_none());
+        // A prefix covering the full depth is a single leaf, not a slice.
+        assert!(x.slice(&[1, 1, 1, 2]).is_none());
+    }
+
+    #[test]
+    fn test_slice_empty_prefix_is_a_clone() {
+        let x = sample_csf();
+        let sliced = x.slice(&[]).unwrap();
+        assert_eq!(x.vals, sliced.vals);
+        assert_eq!(x.fids, sliced.fids);
+        assert_eq!(x.fptr, sliced.fptr);
+    }
 }
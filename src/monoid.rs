@@ -0,0 +1,78 @@
+use std::marker::PhantomData;
+
+// Associative op with an identity, so reduce_column can fold through +, max, min, * etc.
+pub trait Monoid {
+    type Item;
+    fn unit() -> Self::Item;
+    fn op(a: Self::Item, b: Self::Item) -> Self::Item;
+}
+
+// op(x, x) == x, so reduce_column_idempotent can skip weight-based repetition.
+pub trait IdempotentMonoid: Monoid {}
+
+pub struct Sum<T>(PhantomData<T>);
+pub struct Product<T>(PhantomData<T>);
+pub struct Max<T>(PhantomData<T>);
+pub struct Min<T>(PhantomData<T>);
+
+macro_rules! impl_sum_product {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl Monoid for Sum<$t> {
+                type Item = $t;
+                fn unit() -> $t { 0 as $t }
+                fn op(a: $t, b: $t) -> $t { a + b }
+            }
+
+            impl Monoid for Product<$t> {
+                type Item = $t;
+                fn unit() -> $t { 1 as $t }
+                fn op(a: $t, b: $t) -> $t { a * b }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_max_min_bounded {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl Monoid for Max<$t> {
+                type Item = $t;
+                fn unit() -> $t { <$t>::MIN }
+                fn op(a: $t, b: $t) -> $t { if a >= b { a } else { b } }
+            }
+            impl IdempotentMonoid for Max<$t> {}
+
+            impl Monoid for Min<$t> {
+                type Item = $t;
+                fn unit() -> $t { <$t>::MAX }
+                fn op(a: $t, b: $t) -> $t { if a <= b { a } else { b } }
+            }
+            impl IdempotentMonoid for Min<$t> {}
+        )*
+    };
+}
+
+macro_rules! impl_max_min_float {
+    ($($t:ty),* $(,)?) => {
+        $(
+            impl Monoid for Max<$t> {
+                type Item = $t;
+                fn unit() -> $t { <$t>::NEG_INFINITY }
+                fn op(a: $t, b: $t) -> $t { a.max(b) }
+            }
+            impl IdempotentMonoid for Max<$t> {}
+
+            impl Monoid for Min<$t> {
+                type Item = $t;
+                fn unit() -> $t { <$t>::INFINITY }
+                fn op(a: $t, b: $t) -> $t { a.min(b) }
+            }
+            impl IdempotentMonoid for Min<$t> {}
+        )*
+    };
+}
+
+impl_sum_product!(i32, i64, u32, u64, usize, f32, f64);
+impl_max_min_bounded!(i32, i64, u32, u64, usize);
+impl_max_min_float!(f32, f64);